@@ -1,16 +1,42 @@
 #[ink::contract]
 mod erc721 {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::DefaultEnvironment;
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
     use scale::{Decode, Encode};
 
+    // selector of `on_received(operator, from, id, data) -> bool` on the receiving contract
+    const ON_RECEIVED_SELECTOR: [u8; 4] = ink::selector_bytes!("on_received");
+
     pub type TokenId = u32;
+    // the approved spender for a token, and when that approval lapses (if ever)
+    pub type TokenApproval = (AccountId, Option<Expiration>);
+    // (owner, operator) pair key for an approval-for-all grant
+    pub type OperatorApprovalKey = (AccountId, AccountId);
+
     #[ink(storage)]
     #[derive(Default)]
     pub struct Erc721 {
         token_owner: Mapping<TokenId, AccountId>,
-        token_approval: Mapping<TokenId, AccountId>,
+        token_approvals: Mapping<TokenId, TokenApproval>,
         owned_tokens_count: Mapping<AccountId, u32>,
-        operator_approvals: Mapping<(AccountId, AccountId), ()>,
+        operator_approvals: Mapping<OperatorApprovalKey, Option<Expiration>>,
+        name: String,
+        symbol: String,
+        token_uri: Mapping<TokenId, String>,
+        tokens_per_owner: Mapping<AccountId, Vec<TokenId>>,
+        all_tokens: Vec<TokenId>,
+        total_supply: u64,
+        contract_owner: Option<AccountId>,
+        starting_price: Balance,
+        ending_price: Balance,
+        start_block: u32,
+        price_decay_per_block: Balance,
+        minted_count: u32,
+        restrict_mint_to_owner: bool,
+        paused: bool,
     }
 
     #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
@@ -23,13 +49,30 @@ mod erc721 {
         CannotInsert,
         CannotFetchValue,
         NotAllowed,
+        TransferRejected,
+        InsufficientPayment,
+        TransferFailed,
+        NotContractOwner,
+        Paused,
+    }
+
+    // when an approval lapses, modeled on cw721's `Expiration`
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Expiration {
+        AtBlock(u32),
+        AtTime(u64),
+        Never,
     }
 
     #[ink(event)]
     pub struct Transfer {
         #[ink(topic)]
         from: Option<AccountId>,
-        #[int(topic)]
+        #[ink(topic)]
         to: Option<AccountId>,
         #[ink(topic)]
         id: TokenId,
@@ -55,242 +98,676 @@ mod erc721 {
         approved: bool,
     }
 
-    #[ink(constructor)]
-    pub fn new() -> Self {
-        Default::default()
-    }
+    impl Erc721 {
+        #[ink(constructor)]
+        pub fn new(
+            name: String,
+            symbol: String,
+            starting_price: Balance,
+            ending_price: Balance,
+            start_block: u32,
+            price_decay_per_block: Balance,
+            restrict_mint_to_owner: bool,
+        ) -> Self {
+            Self {
+                name,
+                symbol,
+                contract_owner: Some(Self::env().caller()),
+                starting_price,
+                ending_price,
+                start_block,
+                price_decay_per_block,
+                restrict_mint_to_owner,
+                ..Default::default()
+            }
+        }
 
-    #[ink(message)]
-    pub fn balance_of(&self, owner: AccountId) -> u32 {
-        self.balance_of_or_zero(&owner)
-    }
+        // current Dutch-auction price: linearly decays from `starting_price` down to
+        // `ending_price` as blocks pass since `start_block`
+        #[ink(message)]
+        pub fn current_price(&self) -> Balance {
+            let elapsed = self.env().block_number().saturating_sub(self.start_block) as Balance;
+            let decayed = self.price_decay_per_block.saturating_mul(elapsed);
+            self.starting_price
+                .saturating_sub(decayed)
+                .max(self.ending_price)
+        }
 
-    #[ink(message)]
-    pub fn owner_of(&self, id: TokenId) -> Option<AccountId> {
-        self.token_owner.get(id)
-    }
+        // buy the next sequential token at the current auction price, refunding any overpayment
+        // and forwarding proceeds to the contract owner
+        #[ink(message, payable)]
+        pub fn buy(&mut self) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            let price = self.current_price();
+            let paid = self.env().transferred_value();
+            if paid < price {
+                return Err(Error::InsufficientPayment);
+            }
+
+            let caller = self.env().caller();
+
+            // settle payment before touching storage: a failed transfer returns an
+            // `Err` here, and ink! does not roll back storage writes on `Err`, so the
+            // mint below must not happen until the payout is known to have succeeded
+            let refund = paid - price;
+            if refund > 0 {
+                self.env()
+                    .transfer(caller, refund)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+            if let Some(owner) = self.contract_owner {
+                self.env()
+                    .transfer(owner, price)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+
+            // skip past any id a free-form `mint` already claimed so the auction
+            // never collides with it
+            let mut id = self.minted_count;
+            while self.token_owner.contains(id) {
+                id += 1;
+            }
+            self.mint_token(&caller, id)?;
+            self.minted_count = id + 1;
+
+            Ok(())
+        }
 
-    // transfer token from the caller to given destination
-    #[ink(message)]
-    pub fn transfer(&mut self, destination: AccountId, id: TokenId) -> Result<(), Error> {
-        let caller = self.env().caller;
-        self.transfer_token_from(&caller, &destination, id)?;
-        Ok(())
-    }
+        // transfer the owner role to `new_owner`; owner-only
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.contract_owner = Some(new_owner);
+            Ok(())
+        }
 
-    // transfer approved or owned token
-    #[ink(message)]
-    pub fn transfer_from(
-        &mut self,
-        from: AccountId,
-        to: AccountId,
-        id: TokenId,
-    ) -> Result<(), Error> {
-        self.transfer_token_from(&from, &to, id)?;
-        Ok(())
-    }
+        // permanently give up the owner role; owner-only
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.contract_owner = None;
+            Ok(())
+        }
 
-    // return total number of tokens from account
-    fn balance_of_or_zero(&self, of: &AccountId) -> u32 {
-        self.owned_tokens_count.get(of).unwrap_or(0)
-    }
+        // halt minting, transfers and burns; owner-only emergency stop
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = true;
+            Ok(())
+        }
 
-    // transfers token `id` `from` the sender to the `to` `AccountId`
-    fn transfer_token_from(
-        &mut self,
-        from: &AccountId,
-        to: &AccountId,
-        id: TokenId,
-    ) -> Result<(), Error> {
-        let caller = self.env().caller();
-        if !self.exists(id) {
-            return Err(Error::TokenNotFound);
-        };
-        if !self.approved_or_owner(Some(caller), id) {
-            return Err(Error::NotApproved);
-        };
-        self.clear_approval(id);
-        self.remove_token_from(from, id)?;
-        self.add_token_to(to, id)?;
-        self.env().emit_event(Transfer {
-            from: Some(*from),
-            to: Some(*to),
-            id,
-        });
-        Ok(())
-    }
+        // resume minting, transfers and burns; owner-only
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = false;
+            Ok(())
+        }
 
-    // return true if token `id` exists or false if it doesn't
-    fn exists(&self, id: TokenId) -> bool {
-        self.token_owner.contains(id)
-    }
+        // error out unless the caller is the current contract owner
+        fn ensure_owner(&self) -> Result<(), Error> {
+            if self.contract_owner != Some(self.env().caller()) {
+                return Err(Error::NotContractOwner);
+            }
+            Ok(())
+        }
 
-    // return true if the `AccountId` `from` is the owner of token `id`
-    // or it has been approved on behalf of the token `id` owner
-    fn approved_or_owner(&self, from: Option<AccountId>, id: TokenId) -> bool {
-        let owner = self.owner_of(id);
-        from != Some(AccountId::from([0x0; 32]))
-            && (from == owner
-                || from == self.token.approvals.get(id)
-                || self.approved_for_all(
-                    owner.expect("Error with AccountId"),
-                    from.expect("Error with AccountId"),
-                ))
-    }
+        // error out if the contract is currently paused
+        fn ensure_not_paused(&self) -> Result<(), Error> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            Ok(())
+        }
 
-    #[ink(message)]
-    pub fn approve(&mut self, to: AccountId, id: TokenId) -> Result<(), Error> {
-        self.approve_for(&to, id)?;
-        Ok(())
-    }
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId) -> u32 {
+            self.balance_of_or_zero(&owner)
+        }
 
-    // Approves or disapproves the operator for all tokens of the caller.
-    #[ink(message)]
-    pub fn set_approval_for_all(&mut self, to: AccountId, approved: bool) -> Result<(), Error> {
-        self.approve_for_all(to, approved)?;
-        Ok(())
-    }
+        #[ink(message)]
+        pub fn owner_of(&self, id: TokenId) -> Option<AccountId> {
+            self.token_owner.get(id)
+        }
 
-    // Approve the passed `Accountid` to transfer the specified token on behalf of
-    // the message's sender
-    fn approve_for(&mut self, to: &AccountId, id: TokenId) -> Result<(), Error> {
-        let caller = self.env().caller();
-        let owner = self.owner_of(id);
-        if !(owner == Some(caller)
-            || self.approved_for_all(owner.expect("Error with AccountId"), caller))
-        {
-            return Err(Error::NotAllowed);
-        };
-
-        if *to == AccountId::from([0x0; 32]) {
-            return Err(Error::NotAllowed);
-        };
-        if self.token_approvals.contains(id) {
-            return Err(Error::CannotInsert);
-        } else {
-            self.token_approvals.insert(id, to);
-        }
-
-        self.env().emit_event(Approval {
-            from: caller,
-            to: *to,
-            id,
-        });
-        Ok(())
-    }
+        // collection name, set once at construction time
+        #[ink(message)]
+        pub fn name(&self) -> String {
+            self.name.clone()
+        }
 
-    // Approves or disapproves the operator to transfer all tokens of the caller.
-    fn approve_for_all(&mut self, to: AccountId, approved: bool) -> Result<(), Error> {
-        let caller = self.env().caller();
-        if to == caller {
-            return Err(Error::NotAllowed);
+        // collection symbol, set once at construction time
+        #[ink(message)]
+        pub fn symbol(&self) -> String {
+            self.symbol.clone()
         }
 
-        self.env().emit_event(ApprovalForAll {
-            owner: caller,
-            operator: to,
-            approved,
-        });
+        // off-chain metadata URI for a given token, if any was set at mint time
+        #[ink(message)]
+        pub fn token_uri(&self, id: TokenId) -> Option<String> {
+            self.token_uri.get(id)
+        }
 
-        if approved {
-            self.operator_approvals.insert((&caller, &to), &());
-        } else {
-            self.operator_approvals.remove((&caller, &to));
+        // create new token with an associated off-chain metadata URI
+        #[ink(message)]
+        pub fn mint_with_metadata(&mut self, id: TokenId, uri: String) -> Result<(), Error> {
+            self.mint(id)?;
+            self.token_uri.insert(id, &uri);
+            Ok(())
         }
 
-        Ok(())
-    }
+        // total number of tokens currently in existence
+        #[ink(message)]
+        pub fn total_supply(&self) -> u64 {
+            self.total_supply
+        }
 
-    // create new token
-    #[ink(message)]
-    pub fn mint(&mut self, id: TokenId) -> Result<(), Error> {
-        let caller = self.env().caller();
-        self.add_token_to(&caller, id)?;
-        self.env().emit_event(Transfer {
-            from: Some(AccountId::from([0x0; 32])),
-            to: Some(caller),
-            id,
-        });
-        Ok(())
-    }
+        // the `index`-th of the tokens currently in existence, in mint order; burned tokens
+        // are dropped from the list, so indices run from 0 to `total_supply - 1`
+        #[ink(message)]
+        pub fn token_by_index(&self, index: u64) -> Option<TokenId> {
+            self.all_tokens.get(index as usize).copied()
+        }
 
-    // delete existing token. Only owner can burn the token
-    #[ink(message)]
-    pub fn burn(&mut self, id: TokenId) -> Result<(), Error> {
-        let caller = self.env().caller();
-        let self {
-            token_owner,
-            owned_tokens_count,
-            ..
-        } = self;
-
-        let owner = token_owner.get(id).ok_or(Error::TokenNotFound)?;
-        if owner != caller {
-            return Err(Error::NotOwner);
-        };
-
-        let count = owned_tokens_count
-            .get(caller)
-            .map(|c| c - 1)
-            .ok_or(Error::CannotFetchValue)?;
-        owned_tokens_count.insert(caller, &count);
-        token_owner.remove(id);
-
-        self.env().emit_event(Transfer {
-            from: Some(caller),
-            to: Some(AccountId::from([0x0; 32])),
-            id,
-        });
-
-        Ok(())
-    }
+        // up to `limit` of `owner`'s tokens, starting at `from_index`, for paged gallery views
+        #[ink(message)]
+        pub fn tokens_of_owner(
+            &self,
+            owner: AccountId,
+            from_index: u64,
+            limit: u64,
+        ) -> Vec<TokenId> {
+            let owned = self.tokens_per_owner.get(owner).unwrap_or_default();
+            owned
+                .into_iter()
+                .skip(from_index as usize)
+                .take(limit as usize)
+                .collect()
+        }
 
-    // add token `id` to the `to` AccountId
-    fn add_token_to(&mut self, to: &AccountId, id: TokenId) -> Result<(), Error> {
-        let self {
-            token_owner,
-            owned_tokens_count,
-            ..
-        } = self;
+        // transfer token from the caller to given destination
+        #[ink(message)]
+        pub fn transfer(&mut self, destination: AccountId, id: TokenId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.transfer_token_from(&caller, &destination, id)?;
+            Ok(())
+        }
 
-        if token_owner.contains(id) {
-            return Err(Error::TokenExists);
+        // transfer approved or owned token
+        #[ink(message)]
+        pub fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            id: TokenId,
+        ) -> Result<(), Error> {
+            self.transfer_token_from(&from, &to, id)?;
+            Ok(())
         }
 
-        if *to == AccountId::from([0x0, 32]) {
-            return Err(Error::NotAllowed);
-        };
+        // transfer token to `to`, then require it to accept via `on_received`;
+        // reverts the transfer if `to` is a contract that rejects or cannot handle it
+        #[ink(message)]
+        pub fn transfer_from_call(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            id: TokenId,
+            data: Vec<u8>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let prior_approval = self.token_approvals.get(id);
+            self.transfer_token_from(&from, &to, id)?;
+
+            // plain accounts have no `on_received` to call, so they always accept
+            let accepted = if self.env().code_hash(&to).is_err() {
+                true
+            } else {
+                build_call::<DefaultEnvironment>()
+                    .call(to)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ON_RECEIVED_SELECTOR))
+                            .push_arg(caller)
+                            .push_arg(from)
+                            .push_arg(id)
+                            .push_arg(data),
+                    )
+                    .returns::<bool>()
+                    .try_invoke()
+                    .map(|inner| inner.unwrap_or(false))
+                    .unwrap_or(false)
+            };
+
+            if !accepted {
+                self.remove_token_from(&to, id)?;
+                self.add_token_to(&from, id)?;
+                if let Some(approval) = prior_approval {
+                    self.token_approvals.insert(id, &approval);
+                }
+                self.env().emit_event(Transfer {
+                    from: Some(to),
+                    to: Some(from),
+                    id,
+                });
+                return Err(Error::TransferRejected);
+            }
+
+            Ok(())
+        }
 
-        let count = owned_tokens_count.get(to).map(|c| c + 1).unwrap_or(1);
-        owned_tokens_count.insert(to, &count);
-        token_owner.insert(id, to);
+        // return total number of tokens from account
+        fn balance_of_or_zero(&self, of: &AccountId) -> u32 {
+            self.owned_tokens_count.get(of).unwrap_or(0)
+        }
 
-        Ok(())
-    }
+        // transfers token `id` from its owner `from` to the `to` `AccountId`
+        fn transfer_token_from(
+            &mut self,
+            from: &AccountId,
+            to: &AccountId,
+            id: TokenId,
+        ) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let owner = self.owner_of(id).ok_or(Error::TokenNotFound)?;
+            if owner != *from {
+                return Err(Error::NotOwner);
+            };
+            if !self.approved_or_owner(Some(caller), id, Some(owner)) {
+                return Err(Error::NotApproved);
+            };
+            self.clear_approval(id);
+            self.remove_token_from(from, id)?;
+            self.add_token_to(to, id)?;
+            self.env().emit_event(Transfer {
+                from: Some(*from),
+                to: Some(*to),
+                id,
+            });
+            Ok(())
+        }
+
+        // transfer a token the caller owns or is approved for, verifying authorization and
+        // clearing any approval atomically
+        #[ink(message)]
+        pub fn safe_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            id: TokenId,
+        ) -> Result<(), Error> {
+            self.transfer_token_from(&from, &to, id)
+        }
+
+        // return true if `from` is the already-fetched `owner` of token `id`, or it has been
+        // approved (and that approval has not expired) on behalf of that owner
+        fn approved_or_owner(
+            &self,
+            from: Option<AccountId>,
+            id: TokenId,
+            owner: Option<AccountId>,
+        ) -> bool {
+            let approved_for_token = match self.token_approvals.get(id) {
+                Some((approvee, expiration)) if !self.is_expired(expiration) => {
+                    from == Some(approvee)
+                }
+                _ => false,
+            };
+            from != Some(AccountId::from([0x0; 32]))
+                && owner != Some(AccountId::from([0x0; 32]))
+                && (from == owner
+                    || approved_for_token
+                    || self.approved_for_all(
+                        owner.expect("Error with AccountId"),
+                        from.expect("Error with AccountId"),
+                    ))
+        }
+
+        // return true if `operator` has a live (non-expired) approval-for-all from `owner`
+        fn approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
+            match self.operator_approvals.get((&owner, &operator)) {
+                Some(expiration) => !self.is_expired(expiration),
+                None => false,
+            }
+        }
 
-    // remove token `id` from the owner
-    fn remove_token_from(&mut self, from: &AccountId, id: TokenId) -> Result<(), Error> {
-        let self {
-            token_owner,
-            owned_tokens_count,
-            ..
-        } = self;
-
-        if !token_owner.contains(id) {
-            return Err(Error::TokenNotFound);
-        }
-
-        let count = owned_tokens_count
-            .get(from)
-            .map(|c| c - 1)
-            .ok_or(Error::CannotFetchValue)?;
-        owned_tokens_count.insert(from, &count);
-        token_owner.remove(id);
-        Ok(())
+        // true once the current block / timestamp has reached `expiration`
+        fn is_expired(&self, expiration: Option<Expiration>) -> bool {
+            match expiration {
+                None | Some(Expiration::Never) => false,
+                Some(Expiration::AtBlock(block)) => self.env().block_number() >= block,
+                Some(Expiration::AtTime(time)) => self.env().block_timestamp() >= time,
+            }
+        }
+
+        #[ink(message)]
+        pub fn approve(
+            &mut self,
+            to: AccountId,
+            id: TokenId,
+            expires: Option<Expiration>,
+        ) -> Result<(), Error> {
+            self.approve_for(&to, id, expires)?;
+            Ok(())
+        }
+
+        // Approves or disapproves the operator for all tokens of the caller.
+        #[ink(message)]
+        pub fn set_approval_for_all(
+            &mut self,
+            to: AccountId,
+            approved: bool,
+            expires: Option<Expiration>,
+        ) -> Result<(), Error> {
+            self.approve_for_all(to, approved, expires)?;
+            Ok(())
+        }
+
+        // Revoke a previously granted per-token approval.
+        #[ink(message)]
+        pub fn revoke(&mut self, spender: AccountId, id: TokenId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let owner = self.owner_of(id).ok_or(Error::TokenNotFound)?;
+            if !(owner == caller || self.approved_for_all(owner, caller)) {
+                return Err(Error::NotAllowed);
+            };
+            if let Some((approvee, _)) = self.token_approvals.get(id) {
+                if approvee == spender {
+                    self.clear_approval(id);
+                }
+            }
+            Ok(())
+        }
+
+        // Revoke a previously granted approval-for-all.
+        #[ink(message)]
+        pub fn revoke_all(&mut self, operator: AccountId) -> Result<(), Error> {
+            self.approve_for_all(operator, false, None)?;
+            Ok(())
+        }
+
+        // Approve the passed `Accountid` to transfer the specified token on behalf of
+        // the message's sender, optionally until `expires`
+        fn approve_for(
+            &mut self,
+            to: &AccountId,
+            id: TokenId,
+            expires: Option<Expiration>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let owner = self.owner_of(id).ok_or(Error::TokenNotFound)?;
+            if !(owner == caller || self.approved_for_all(owner, caller)) {
+                return Err(Error::NotAllowed);
+            };
+
+            if *to == AccountId::from([0x0; 32]) {
+                return Err(Error::NotAllowed);
+            };
+            self.token_approvals.insert(id, &(*to, expires));
+
+            self.env().emit_event(Approval {
+                from: caller,
+                to: *to,
+                id,
+            });
+            Ok(())
+        }
+
+        // Approves or disapproves the operator to transfer all tokens of the caller,
+        // optionally until `expires`.
+        fn approve_for_all(
+            &mut self,
+            to: AccountId,
+            approved: bool,
+            expires: Option<Expiration>,
+        ) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if to == caller {
+                return Err(Error::NotAllowed);
+            }
+
+            self.env().emit_event(ApprovalForAll {
+                owner: caller,
+                operator: to,
+                approved,
+            });
+
+            if approved {
+                self.operator_approvals.insert((&caller, &to), &expires);
+            } else {
+                self.operator_approvals.remove((&caller, &to));
+            }
+
+            Ok(())
+        }
+
+        // create new token
+        #[ink(message)]
+        pub fn mint(&mut self, id: TokenId) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            if self.restrict_mint_to_owner {
+                self.ensure_owner()?;
+            }
+            self.mint_token(&caller, id)?;
+            Ok(())
+        }
+
+        // add token `id` to `to`'s balance, updating the enumeration index and emitting `Transfer`
+        fn mint_token(&mut self, to: &AccountId, id: TokenId) -> Result<(), Error> {
+            self.add_token_to(to, id)?;
+            self.all_tokens.push(id);
+            self.total_supply += 1;
+            self.env().emit_event(Transfer {
+                from: Some(AccountId::from([0x0; 32])),
+                to: Some(*to),
+                id,
+            });
+            Ok(())
+        }
+
+        // delete existing token. Only owner can burn the token
+        #[ink(message)]
+        pub fn burn(&mut self, id: TokenId) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let Self {
+                token_owner,
+                owned_tokens_count,
+                ..
+            } = self;
+
+            let owner = token_owner.get(id).ok_or(Error::TokenNotFound)?;
+            if owner != caller {
+                return Err(Error::NotOwner);
+            };
+
+            let count = owned_tokens_count
+                .get(caller)
+                .map(|c| c - 1)
+                .ok_or(Error::CannotFetchValue)?;
+            owned_tokens_count.insert(caller, &count);
+            token_owner.remove(id);
+            self.token_uri.remove(id);
+            self.remove_owned_token(&caller, id);
+            self.all_tokens.retain(|&t| t != id);
+            self.total_supply -= 1;
+
+            self.env().emit_event(Transfer {
+                from: Some(caller),
+                to: Some(AccountId::from([0x0; 32])),
+                id,
+            });
+
+            Ok(())
+        }
+
+        // add token `id` to the `to` AccountId
+        fn add_token_to(&mut self, to: &AccountId, id: TokenId) -> Result<(), Error> {
+            let Self {
+                token_owner,
+                owned_tokens_count,
+                ..
+            } = self;
+
+            if token_owner.contains(id) {
+                return Err(Error::TokenExists);
+            }
+
+            if *to == AccountId::from([0x0; 32]) {
+                return Err(Error::NotAllowed);
+            };
+
+            let count = owned_tokens_count.get(to).map(|c| c + 1).unwrap_or(1);
+            owned_tokens_count.insert(to, &count);
+            token_owner.insert(id, to);
+            self.push_owned_token(to, id);
+
+            Ok(())
+        }
+
+        // record that `to` now holds token `id` in the enumeration index
+        fn push_owned_token(&mut self, to: &AccountId, id: TokenId) {
+            let mut owned = self.tokens_per_owner.get(to).unwrap_or_default();
+            owned.push(id);
+            self.tokens_per_owner.insert(to, &owned);
+        }
+
+        // drop token `id` from `from`'s entry in the enumeration index
+        fn remove_owned_token(&mut self, from: &AccountId, id: TokenId) {
+            let mut owned = self.tokens_per_owner.get(from).unwrap_or_default();
+            owned.retain(|&t| t != id);
+            self.tokens_per_owner.insert(from, &owned);
+        }
+
+        // remove token `id` from the owner
+        fn remove_token_from(&mut self, from: &AccountId, id: TokenId) -> Result<(), Error> {
+            let Self {
+                token_owner,
+                owned_tokens_count,
+                ..
+            } = self;
+
+            if !token_owner.contains(id) {
+                return Err(Error::TokenNotFound);
+            }
+
+            let count = owned_tokens_count
+                .get(from)
+                .map(|c| c - 1)
+                .ok_or(Error::CannotFetchValue)?;
+            owned_tokens_count.insert(from, &count);
+            token_owner.remove(id);
+            self.remove_owned_token(from, id);
+            Ok(())
+        }
+
+        // remove existing approval from token `id`
+        fn clear_approval(&mut self, id: TokenId) {
+            self.token_approvals.remove(id);
+        }
     }
 
-    // remove existing approval from token `id`
-    fn clear_approval(&mut self, id: TokenId) {
-        self.token_approvals.remove(id);
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test;
+
+        fn set_caller(caller: AccountId) {
+            test::set_caller::<ink::env::DefaultEnvironment>(caller);
+        }
+
+        fn new_contract() -> Erc721 {
+            Erc721::new(String::from("Test"), String::from("TST"), 0, 0, 0, 0, false)
+        }
+
+        #[ink::test]
+        fn owner_can_transfer() {
+            let accounts = test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            let mut erc721 = new_contract();
+            erc721.mint(1).unwrap();
+
+            assert!(erc721.transfer(accounts.bob, 1).is_ok());
+            assert_eq!(erc721.owner_of(1), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn approvee_can_transfer() {
+            let accounts = test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            let mut erc721 = new_contract();
+            erc721.mint(1).unwrap();
+            erc721.approve(accounts.bob, 1, None).unwrap();
+
+            set_caller(accounts.bob);
+            assert!(erc721
+                .transfer_from(accounts.alice, accounts.charlie, 1)
+                .is_ok());
+            assert_eq!(erc721.owner_of(1), Some(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn operator_can_transfer() {
+            let accounts = test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            let mut erc721 = new_contract();
+            erc721.mint(1).unwrap();
+            erc721
+                .set_approval_for_all(accounts.bob, true, None)
+                .unwrap();
+
+            set_caller(accounts.bob);
+            assert!(erc721
+                .transfer_from(accounts.alice, accounts.charlie, 1)
+                .is_ok());
+            assert_eq!(erc721.owner_of(1), Some(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn expired_approval_is_rejected() {
+            let accounts = test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            let mut erc721 = new_contract();
+            erc721.mint(1).unwrap();
+            erc721
+                .approve(accounts.bob, 1, Some(Expiration::AtBlock(1)))
+                .unwrap();
+
+            // advance past block 1 so the approval has lapsed
+            test::advance_block::<ink::env::DefaultEnvironment>();
+            test::advance_block::<ink::env::DefaultEnvironment>();
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc721.transfer_from(accounts.alice, accounts.charlie, 1),
+                Err(Error::NotApproved)
+            );
+        }
+
+        #[ink::test]
+        fn zero_address_is_never_authorized() {
+            let accounts = test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            let mut erc721 = new_contract();
+            erc721.mint(1).unwrap();
+
+            let zero = AccountId::from([0x0; 32]);
+            let owner = erc721.owner_of(1);
+            assert!(!erc721.approved_or_owner(Some(zero), 1, owner));
+        }
+
+        #[ink::test]
+        fn approving_a_nonexistent_token_errors_instead_of_panicking() {
+            let accounts = test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            let mut erc721 = new_contract();
+
+            assert_eq!(
+                erc721.approve(accounts.bob, 1, None),
+                Err(Error::TokenNotFound)
+            );
+        }
     }
 }